@@ -0,0 +1,142 @@
+use std::{
+    cell::RefCell,
+    fmt,
+    rc::{Rc, Weak},
+};
+
+use super::{
+    distributed_cert::DistributedCert, distributed_private_key::DistributedPrivateKey, distributed_public_key::DistributedPublicKey,
+    signee::Signee,
+};
+use crate::{k8s_etcd::InMemoryK8sEtcd, rsa_key_pool::RsaKeyPool};
+
+pub(crate) struct CertKeyPair {
+    pub(crate) distributed_private_key: Option<Rc<RefCell<DistributedPrivateKey>>>,
+    pub(crate) distributed_cert: Rc<RefCell<DistributedCert>>,
+    pub(crate) signer: Option<Rc<RefCell<CertKeyPair>>>,
+    pub(crate) signees: Vec<Signee>,
+    pub(crate) associated_public_key: Option<Rc<RefCell<DistributedPublicKey>>>,
+    pub(crate) regenerated: bool,
+    /// A handle back to our own `Rc`, so that regenerating a signee can pass its signer along as
+    /// an `Rc` (recursion needs to hand out a reference-counted pointer to `self`, which a plain
+    /// `&mut self` method can't conjure up on its own). Set once at construction via
+    /// `Rc::new_cyclic` and never changes afterwards.
+    pub(crate) self_weak: Weak<RefCell<CertKeyPair>>,
+}
+
+impl CertKeyPair {
+    /// Regenerate this cert-key pair: mint a fresh key, build a new certificate around it (using
+    /// `sign_with` as the issuer, or self-signing if `None`), re-emit the original cert's critical
+    /// extensions onto it, sign it, and - if it has an issuer - validate that the result still
+    /// satisfies that issuer's `NameConstraints`. Then recurse into every signee so the whole
+    /// chain is regenerated bottom-up... well, top-down, with each new cert becoming the signer
+    /// for the next layer.
+    pub(crate) fn regenerate(&mut self, sign_with: Option<Rc<RefCell<CertKeyPair>>>, rsa_key_pool: &mut RsaKeyPool) {
+        let new_private_key = rsa_key_pool.get();
+
+        let mut builder = openssl::x509::X509::builder().expect("failed to create X509 builder");
+        builder
+            .set_subject_name((*self.distributed_cert).borrow().certificate.original_subject_name())
+            .expect("failed to set subject name");
+        builder
+            .set_not_before((*self.distributed_cert).borrow().certificate.original_not_before())
+            .expect("failed to set notBefore");
+        builder
+            .set_not_after((*self.distributed_cert).borrow().certificate.original_not_after())
+            .expect("failed to set notAfter");
+        builder.set_pubkey(&new_private_key.public_pkey()).expect("failed to set public key");
+
+        match &sign_with {
+            Some(signer) => {
+                let issuer_cert = (*signer).borrow();
+                builder
+                    .set_issuer_name((*issuer_cert.distributed_cert).borrow().certificate.original_subject_name())
+                    .expect("failed to set issuer name");
+            }
+            // No signer means this cert is self-signed (`fill_cert_key_signers` panics for any
+            // other cert it can't find a signer for), so it issues itself.
+            None => {
+                builder
+                    .set_issuer_name((*self.distributed_cert).borrow().certificate.original_subject_name())
+                    .expect("failed to set issuer name");
+            }
+        }
+
+        // Carry over the critical extensions we found on the original cert - NameConstraints in
+        // particular - plus SubjectAlternativeName even if it was non-critical, instead of
+        // silently dropping them on the regenerated one.
+        (*self.distributed_cert).borrow().reapply_carried_extensions(&mut builder);
+
+        let signing_key = match &sign_with {
+            Some(signer) => (*signer)
+                .borrow()
+                .distributed_private_key
+                .as_ref()
+                .expect("a cert with a signer must have a signing key")
+                .borrow()
+                .key
+                .clone(),
+            None => new_private_key.private_key().clone(),
+        };
+        builder
+            .sign(&signing_key.to_pkey(), openssl::hash::MessageDigest::sha256())
+            .expect("failed to sign regenerated certificate");
+
+        let new_cert = builder.build();
+
+        (*self.distributed_cert).borrow_mut().certificate.original = x509_certificate::X509Certificate::from_der(
+            new_cert.to_der().expect("regenerated certificate must re-encode to DER"),
+        )
+        .expect("regenerated certificate must re-parse");
+
+        // Validate the regenerated cert - not the pre-regeneration one - against whatever
+        // NameConstraints its issuer was scoped with. This has to happen after the
+        // `certificate.original` swap above, or it's checking the wrong cert's SANs entirely.
+        if let Some(signer) = &sign_with {
+            if let Err(message) =
+                (*self.distributed_cert)
+                    .borrow()
+                    .validate_against_issuer_constraints(&(*(*signer).borrow().distributed_cert).borrow())
+            {
+                panic!("{message}");
+            }
+        }
+
+        if let Some(distributed_private_key) = &self.distributed_private_key {
+            (**distributed_private_key).borrow_mut().key = new_private_key.private_key().clone();
+            (**distributed_private_key).borrow_mut().regenerated = true;
+        }
+
+        self.regenerated = true;
+
+        let self_rc = self.self_weak.upgrade().expect("CertKeyPair always has a living self-reference to itself");
+        for signee in self.signees.clone() {
+            match signee {
+                Signee::CertKeyPair(pair) => (*pair).borrow_mut().regenerate(Some(Rc::clone(&self_rc)), rsa_key_pool),
+                Signee::Jwt(jwt) => (*jwt).borrow_mut().regenerate_with_signer(&self_rc),
+            }
+        }
+    }
+
+    pub(crate) async fn commit_to_etcd_and_disk(&self, etcd_client: &InMemoryK8sEtcd) {
+        (*self.distributed_cert).borrow().commit_to_etcd_and_disk(etcd_client).await;
+        if let Some(distributed_private_key) = &self.distributed_private_key {
+            (**distributed_private_key).borrow().commit_to_etcd_and_disk(etcd_client).await;
+        }
+    }
+}
+
+impl fmt::Display for CertKeyPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CertKeyPair {{ cert: {} }}", (*self.distributed_cert).borrow().locations)
+    }
+}
+
+impl PartialEq for CertKeyPair {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(
+            &self.self_weak.upgrade().expect("always live"),
+            &other.self_weak.upgrade().expect("always live"),
+        )
+    }
+}
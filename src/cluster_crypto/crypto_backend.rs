@@ -0,0 +1,190 @@
+use super::keys::{EcCurve, EcPublicKey, PublicKey, RsaKey};
+use x509_certificate::X509Certificate;
+
+/// The set of DER/PEM encode-decode and signing operations that the rest of `cluster_crypto`
+/// needs, abstracted behind a trait so the concrete implementation can be swapped at build time
+/// without touching the scanning/cataloguing logic that calls into it. `crypto_utils` is the only
+/// module that's supposed to know which backend is active; everything else in `cluster_crypto`
+/// calls through `crypto_utils`'s free functions as it already does.
+pub(crate) trait CryptoBackend {
+    /// Returns true if `candidate_signer`'s cert signed `cert`. Used as the fallback for
+    /// signature algorithms `x509_certificate::X509Certificate::verify_signed_by_certificate`
+    /// doesn't support natively.
+    fn is_signed_by(&self, candidate_signer: &X509Certificate, cert: &X509Certificate) -> bool;
+
+    /// Verify an RS256/RS384/RS512/ES256/ES384 signature over `message` against `public_key`,
+    /// hashed with `digest`. `digest` is the caller's responsibility to pick - for a JWT it comes
+    /// straight from the `alg` header, since RSA's digest isn't implied by the key the way an EC
+    /// curve implies its digest.
+    fn verify_asymmetric_signature(&self, public_key: &PublicKey, digest: Digest, message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// The hash algorithm to use when verifying a signature. Kept backend-agnostic (rather than e.g.
+/// `openssl::hash::MessageDigest`) so `crypto_utils` can pick one without depending on which
+/// `CryptoBackend` is linked in.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Digest {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+/// The digest a key implies on its own, for call sites (like general X.509 chain verification)
+/// that have no more specific algorithm identifier to go on. RSA defaults to SHA-256 (the
+/// overwhelmingly common case); EC keys default to the digest their curve is conventionally paired
+/// with (P-256/SHA-256, P-384/SHA-384, matching ES256/ES384).
+fn default_digest_for_key(public_key: &PublicKey) -> Digest {
+    match public_key {
+        PublicKey::Rsa(_) => Digest::Sha256,
+        PublicKey::Ec(ec) => match ec.curve {
+            EcCurve::P256 => Digest::Sha256,
+            EcCurve::P384 => Digest::Sha384,
+        },
+    }
+}
+
+/// The backend linked into the binary, chosen at compile time. Defaults to the OpenSSL-backed
+/// implementation recert has always used; build with `--features pure-rust-crypto` to verify
+/// signatures with the `rsa`/`p256`/`p384`/`sha2` crates instead. OpenSSL stays linked either way:
+/// PEM/PKCS#8 decoding (`crypto_objects`) and certificate regeneration (`cert_key_pair`,
+/// `distributed_cert`) still use it directly.
+pub(crate) fn active_backend() -> &'static dyn CryptoBackend {
+    #[cfg(feature = "pure-rust-crypto")]
+    {
+        &RustCryptoBackend
+    }
+    #[cfg(not(feature = "pure-rust-crypto"))]
+    {
+        &OpensslBackend
+    }
+}
+
+struct OpensslBackend;
+
+impl CryptoBackend for OpensslBackend {
+    fn is_signed_by(&self, candidate_signer: &X509Certificate, cert: &X509Certificate) -> bool {
+        let signer_pkey =
+            openssl::x509::X509::from_der(candidate_signer.encode_der().expect("cert always re-encodes").as_slice()).expect("corrupt cert");
+        let cert_pkey = openssl::x509::X509::from_der(cert.encode_der().expect("cert always re-encodes").as_slice()).expect("corrupt cert");
+
+        cert_pkey
+            .verify(&signer_pkey.public_key().expect("corrupt public key"))
+            .unwrap_or(false)
+    }
+
+    fn verify_asymmetric_signature(&self, public_key: &PublicKey, digest: Digest, message: &[u8], signature: &[u8]) -> bool {
+        let pkey = match public_key {
+            PublicKey::Rsa(RsaKey(der)) => {
+                let rsa = openssl::rsa::Rsa::public_key_from_der_pkcs1(der).expect("valid RSA key");
+                openssl::pkey::PKey::from_rsa(rsa).expect("valid RSA key")
+            }
+            PublicKey::Ec(ec) => {
+                let group = openssl::ec::EcGroup::from_curve_name(match ec.curve {
+                    EcCurve::P256 => openssl::nid::Nid::X9_62_PRIME256V1,
+                    EcCurve::P384 => openssl::nid::Nid::SECP384R1,
+                })
+                .expect("supported curve");
+                let mut ctx = openssl::bn::BigNumContext::new().expect("failed to allocate bignum context");
+                let point = openssl::ec::EcPoint::from_bytes(&group, &ec.point, &mut ctx).expect("corrupt EC point");
+                let ec_key = openssl::ec::EcKey::from_public_key(&group, &point).expect("corrupt EC key");
+                openssl::pkey::PKey::from_ec_key(ec_key).expect("valid EC key")
+            }
+        };
+
+        let digest = match digest {
+            Digest::Sha256 => openssl::hash::MessageDigest::sha256(),
+            Digest::Sha384 => openssl::hash::MessageDigest::sha384(),
+            Digest::Sha512 => openssl::hash::MessageDigest::sha512(),
+        };
+
+        let mut verifier = openssl::sign::Verifier::new(digest, &pkey).expect("failed to create verifier");
+        verifier.update(message).expect("failed to feed verifier");
+        verifier.verify(signature).unwrap_or(false)
+    }
+}
+
+/// Pure-Rust alternative to `OpensslBackend`: reads certificates through `x509_certificate` and
+/// verifies signatures with `rsa`/`p256`/`p384`, without calling into OpenSSL. It only replaces
+/// signature verification; the rest of `cluster_crypto` still uses OpenSSL for PEM/PKCS#8
+/// decoding and certificate regeneration, so the binary still links OpenSSL.
+#[cfg(feature = "pure-rust-crypto")]
+struct RustCryptoBackend;
+
+#[cfg(feature = "pure-rust-crypto")]
+impl CryptoBackend for RustCryptoBackend {
+    fn is_signed_by(&self, candidate_signer: &X509Certificate, cert: &X509Certificate) -> bool {
+        let message = cert.constructed_data();
+        let signature = cert.signature();
+
+        let public_key = public_key_from_cert(candidate_signer);
+        let digest = default_digest_for_key(&public_key);
+        self.verify_asymmetric_signature(&public_key, digest, &message, &signature)
+    }
+
+    fn verify_asymmetric_signature(&self, public_key: &PublicKey, digest: Digest, message: &[u8], signature: &[u8]) -> bool {
+        use rsa::signature::Verifier as _;
+
+        match public_key {
+            PublicKey::Rsa(RsaKey(der)) => {
+                use rsa::pkcs1::DecodeRsaPublicKey;
+                let rsa_public_key = rsa::RsaPublicKey::from_pkcs1_der(der).expect("corrupt RSA key");
+                let signature = match rsa::pkcs1v15::Signature::try_from(signature) {
+                    Ok(signature) => signature,
+                    Err(_) => return false,
+                };
+                match digest {
+                    Digest::Sha256 => rsa::pkcs1v15::VerifyingKey::<sha2::Sha256>::new(rsa_public_key)
+                        .verify(message, &signature)
+                        .is_ok(),
+                    Digest::Sha384 => rsa::pkcs1v15::VerifyingKey::<sha2::Sha384>::new(rsa_public_key)
+                        .verify(message, &signature)
+                        .is_ok(),
+                    Digest::Sha512 => rsa::pkcs1v15::VerifyingKey::<sha2::Sha512>::new(rsa_public_key)
+                        .verify(message, &signature)
+                        .is_ok(),
+                }
+            }
+            PublicKey::Ec(ec) => match ec.curve {
+                EcCurve::P256 => {
+                    let Ok(verifying_key) = p256::ecdsa::VerifyingKey::from_sec1_bytes(&ec.point) else {
+                        return false;
+                    };
+                    let Ok(signature) = p256::ecdsa::Signature::from_der(signature) else {
+                        return false;
+                    };
+                    verifying_key.verify(message, &signature).is_ok()
+                }
+                EcCurve::P384 => {
+                    let Ok(verifying_key) = p384::ecdsa::VerifyingKey::from_sec1_bytes(&ec.point) else {
+                        return false;
+                    };
+                    let Ok(signature) = p384::ecdsa::Signature::from_der(signature) else {
+                        return false;
+                    };
+                    verifying_key.verify(message, &signature).is_ok()
+                }
+            },
+        }
+    }
+}
+
+/// Turn a certificate's `SubjectPublicKeyInfo` into our own [`PublicKey`]. The SPKI
+/// `subjectPublicKey` bit string content is already exactly what we need for either key shape -
+/// a PKCS#1 `RSAPublicKey` DER for RSA, or the raw SEC1 point for EC - so no further unwrapping
+/// is needed beyond picking the right variant from the cert's advertised key algorithm.
+#[cfg(feature = "pure-rust-crypto")]
+fn public_key_from_cert(cert: &X509Certificate) -> PublicKey {
+    let raw_key = cert.public_key_data().to_vec();
+
+    match cert.key_algorithm().expect("signer cert must carry a recognized key algorithm to verify a signature against it") {
+        x509_certificate::KeyAlgorithm::Rsa => PublicKey::Rsa(RsaKey(raw_key)),
+        x509_certificate::KeyAlgorithm::Ecdsa(curve) => PublicKey::Ec(EcPublicKey {
+            curve: match curve {
+                x509_certificate::EcdsaCurve::Secp256r1 => EcCurve::P256,
+                x509_certificate::EcdsaCurve::Secp384r1 => EcCurve::P384,
+            },
+            point: raw_key,
+        }),
+        other => panic!("unsupported key algorithm for signature verification: {:?}", other),
+    }
+}
@@ -0,0 +1,110 @@
+use super::{
+    certificate::Certificate,
+    jwt::Jwt,
+    keys::{EcCurve, EcPrivateKey, PrivateKey, PublicKey, RsaKey},
+    locations::Location,
+};
+
+/// A crypto object found while scanning, plus the location it was found at.
+pub(crate) struct DiscoveredCryptoObect {
+    pub(crate) location: Location,
+    pub(crate) crypto_object: CryptoObject,
+}
+
+/// Every individual crypto object that can end up embedded in a PEM block or file on disk. The
+/// `PrivateKey`/`PublicKey` case carries both halves (when both are known at parse time) so
+/// callers don't need to re-derive the public half from the private one themselves.
+pub(crate) enum CryptoObject {
+    PrivateKey(PrivateKey, PublicKey),
+    PublicKey(PublicKey),
+    Certificate(Certificate),
+    Jwt(Jwt),
+}
+
+/// Parse a single PEM block into a crypto object, recognizing RSA and EC (P-256/P-384) private
+/// keys in both their legacy single-algorithm PEM form (`RSA PRIVATE KEY`/`EC PRIVATE KEY`) and
+/// PKCS#8 form, plus standalone public keys and certificates.
+///
+/// This still leans on `openssl` as a decoding library to pick PEM/PKCS#8 blocks apart - that part
+/// isn't backend-gated, since it's unrelated to which `CryptoBackend` ends up verifying
+/// signatures. What *does* matter is that none of the `keys::{PrivateKey,PublicKey}` values handed
+/// back ever retain an `openssl` type: every RSA key is immediately flattened to its PKCS#1 DER
+/// encoding (see [`keys::RsaKey`]), so key bookkeeping in `cluster_crypto` - and a
+/// `pure-rust-crypto` build's signature verification - never touches `openssl` types. Certificate
+/// regeneration (`cert_key_pair`, `distributed_cert`) still builds and signs with `openssl`.
+pub(crate) fn process_pem(pem: &pem::Pem) -> Option<CryptoObject> {
+    match pem.tag() {
+        "RSA PRIVATE KEY" => {
+            openssl::rsa::Rsa::private_key_from_der(pem.contents()).expect("corrupt RSA private key");
+            let private_key = PrivateKey::Rsa(RsaKey(pem.contents().to_vec()));
+            let public_key = PublicKey::from(&private_key);
+            Some(CryptoObject::PrivateKey(private_key, public_key))
+        }
+        "EC PRIVATE KEY" => Some(parse_ec_sec1_private_key(pem.contents())),
+        "PRIVATE KEY" => Some(parse_pkcs8_private_key(pem.contents())),
+        "PUBLIC KEY" | "RSA PUBLIC KEY" => Some(CryptoObject::PublicKey(parse_public_key(pem.contents()))),
+        "CERTIFICATE" => Some(CryptoObject::Certificate(Certificate::from_der(pem.contents()))),
+        _ => None,
+    }
+}
+
+fn parse_ec_sec1_private_key(der: &[u8]) -> CryptoObject {
+    let ec_key = openssl::ec::EcKey::private_key_from_der(der).expect("corrupt EC private key");
+    let curve = ec_curve_from_group(ec_key.group());
+    let scalar = ec_key.private_key().to_vec();
+
+    let private_key = PrivateKey::Ec(EcPrivateKey { curve, scalar });
+    let public_key = PublicKey::from(&private_key);
+    CryptoObject::PrivateKey(private_key, public_key)
+}
+
+fn parse_pkcs8_private_key(der: &[u8]) -> CryptoObject {
+    let pkey = openssl::pkey::PKey::private_key_from_der(der).expect("corrupt PKCS#8 private key");
+
+    let private_key = match pkey.id() {
+        openssl::pkey::Id::RSA => {
+            let der = pkey.rsa().expect("RSA key id implies an RSA key").private_key_to_der().expect("valid RSA key");
+            PrivateKey::Rsa(RsaKey(der))
+        }
+        openssl::pkey::Id::EC => {
+            let ec_key = pkey.ec_key().expect("EC key id implies an EC key");
+            let curve = ec_curve_from_group(ec_key.group());
+            let scalar = ec_key.private_key().to_vec();
+            PrivateKey::Ec(EcPrivateKey { curve, scalar })
+        }
+        other => panic!("unsupported PKCS#8 private key algorithm {:?}", other),
+    };
+
+    let public_key = PublicKey::from(&private_key);
+    CryptoObject::PrivateKey(private_key, public_key)
+}
+
+fn parse_public_key(der: &[u8]) -> PublicKey {
+    let pkey = openssl::pkey::PKey::public_key_from_der(der).expect("corrupt public key");
+
+    match pkey.id() {
+        openssl::pkey::Id::RSA => {
+            let der = pkey.rsa().expect("RSA key id implies an RSA key").public_key_to_der_pkcs1().expect("valid RSA key");
+            PublicKey::Rsa(RsaKey(der))
+        }
+        openssl::pkey::Id::EC => {
+            let ec_key = pkey.ec_key().expect("EC key id implies an EC key");
+            let curve = ec_curve_from_group(ec_key.group());
+            let mut ctx = openssl::bn::BigNumContext::new().expect("failed to allocate bignum context");
+            let point = ec_key
+                .public_key()
+                .to_bytes(ec_key.group(), openssl::ec::PointConversionForm::UNCOMPRESSED, &mut ctx)
+                .expect("failed to serialize EC point");
+            PublicKey::Ec(super::keys::EcPublicKey { curve, point })
+        }
+        other => panic!("unsupported public key algorithm {:?}", other),
+    }
+}
+
+fn ec_curve_from_group(group: &openssl::ec::EcGroupRef) -> EcCurve {
+    match group.curve_name() {
+        Some(openssl::nid::Nid::X9_62_PRIME256V1) => EcCurve::P256,
+        Some(openssl::nid::Nid::SECP384R1) => EcCurve::P384,
+        other => panic!("unsupported EC curve {:?} - recert only supports P-256 and P-384", other),
+    }
+}
@@ -0,0 +1,119 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::{
+    cert_key_pair::CertKeyPair,
+    crypto_backend::{self, CryptoBackend},
+    distributed_jwt::DistributedJwt,
+    jwt::JwtSigner,
+    keys::PublicKey,
+};
+
+/// Extract the Subject Key Identifier extension (OID 2.5.29.14), hex-encoded, if present. The
+/// extnValue is itself a DER `OCTET STRING` wrapping the raw key id - we unwrap that before
+/// encoding so this is directly comparable to `authority_key_identifier`'s output.
+pub(crate) fn subject_key_identifier(cert: &x509_certificate::X509Certificate) -> Option<String> {
+    extension_value(cert, EXTENSION_SUBJECT_KEY_IDENTIFIER)
+        .and_then(|extn_value| super::pem_utils::subject_key_identifier_bytes(&extn_value))
+        .map(hex::encode)
+}
+
+/// Extract the `keyIdentifier` field of the Authority Key Identifier extension (OID
+/// 2.5.29.35), hex-encoded, if present. We only use the `keyIdentifier` choice (not the
+/// issuer+serial form), unwrapped out of the extension's `SEQUENCE { [0] keyIdentifier, ... }`
+/// so the raw bytes are comparable to `subject_key_identifier`'s output.
+pub(crate) fn authority_key_identifier(cert: &x509_certificate::X509Certificate) -> Option<String> {
+    extension_value(cert, EXTENSION_AUTHORITY_KEY_IDENTIFIER)
+        .and_then(|extn_value| super::pem_utils::authority_key_identifier_bytes(&extn_value))
+        .map(hex::encode)
+}
+
+/// Every extension on a certificate that needs to survive regeneration verbatim: every critical
+/// extension, plus `SubjectAlternativeName` even when it's non-critical (the common case) - a
+/// regenerated cert built without carrying SANs forward would silently lose them. We keep these as
+/// raw `(oid, critical, der-value)` triples rather than parsing them into first-class types, since
+/// recert only needs to copy them forward, not interpret them.
+pub(crate) fn carried_extensions(
+    cert: &x509_certificate::X509Certificate,
+) -> Vec<(x509_certificate::asn1time::ObjectIdentifier, bool, Vec<u8>)> {
+    cert.extensions()
+        .filter(|extension| extension.critical || extension.id.as_ref() == EXTENSION_SUBJECT_ALT_NAME)
+        .map(|extension| (extension.id.clone(), extension.critical, extension.value.to_vec()))
+        .collect()
+}
+
+const EXTENSION_SUBJECT_KEY_IDENTIFIER: &[u64] = &[2, 5, 29, 14];
+const EXTENSION_AUTHORITY_KEY_IDENTIFIER: &[u64] = &[2, 5, 29, 35];
+const EXTENSION_SUBJECT_ALT_NAME: &[u64] = &[2, 5, 29, 17];
+
+fn extension_value(cert: &x509_certificate::X509Certificate, oid: &[u64]) -> Option<Vec<u8>> {
+    cert.extensions()
+        .find(|extension| extension.id.as_ref() == oid)
+        .map(|extension| extension.value.to_vec())
+}
+
+/// Verify a JWT against whichever key a `JwtSigner` candidate points at. Standalone public keys,
+/// private keys, and the private key half of a cert-key pair are all routed through the same
+/// underlying asymmetric-signature check, so adding a new `JwtSigner` variant only means adding a
+/// branch here, not a new verification path.
+pub(crate) fn verify_jwt_signer(signer: &JwtSigner, jwt: &DistributedJwt) -> Result<(), JwtVerificationError> {
+    let public_key = match signer {
+        JwtSigner::PrivateKey(private_key) => PublicKey::from(&(**private_key).borrow().key),
+        JwtSigner::CertKeyPair(cert_key_pair) => {
+            let distributed_private_key = (**cert_key_pair)
+                .borrow()
+                .distributed_private_key
+                .clone()
+                .ok_or(JwtVerificationError::SignatureMismatch)?;
+            PublicKey::from(&(*distributed_private_key).borrow().key)
+        }
+        JwtSigner::PublicKey(public_key) => (**public_key).borrow().key.clone(),
+        JwtSigner::Unknown | JwtSigner::Ambiguous(_) => return Err(JwtVerificationError::SignatureMismatch),
+    };
+
+    verify_jwt(&public_key, jwt)
+}
+
+/// Verify a JWT's signature against a specific public key, via whichever crypto backend is active.
+/// The digest is taken from the JWT's own `alg` header rather than assumed from the key - RS256,
+/// RS384, and RS512 all sign with an RSA key, and only the header tells them apart.
+pub(crate) fn verify_jwt(public_key: &PublicKey, jwt: &DistributedJwt) -> Result<(), JwtVerificationError> {
+    let (signing_input, signature) = jwt.jwt.signing_input_and_signature();
+    let digest = digest_for_jwt_alg(jwt.jwt.alg());
+
+    if crypto_backend::active_backend().verify_asymmetric_signature(public_key, digest, signing_input, signature) {
+        Ok(())
+    } else {
+        Err(JwtVerificationError::SignatureMismatch)
+    }
+}
+
+/// Map a JWS `alg` header value to the digest it signs with. Only the asymmetric algs recert
+/// actually verifies signatures for are handled here - `fill_jwt_signers` already filters
+/// candidates down to these via `jwt::alg_is_supported`/`jwt::alg_matches_signer` before calling
+/// `verify_jwt_signer`, so reaching an unhandled alg here would mean that filtering let something
+/// through it shouldn't have.
+fn digest_for_jwt_alg(alg: &str) -> crypto_backend::Digest {
+    match alg {
+        "RS256" | "ES256" => crypto_backend::Digest::Sha256,
+        "RS384" | "ES384" => crypto_backend::Digest::Sha384,
+        "RS512" => crypto_backend::Digest::Sha512,
+        other => panic!("unsupported JWT signing algorithm {other}"),
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum JwtVerificationError {
+    SignatureMismatch,
+}
+
+/// Whether `cert` is signed by `candidate_signer`, going through whichever crypto backend is
+/// configured for this build rather than calling OpenSSL directly. This is the fallback path for
+/// the handful of signature algorithms `x509_certificate::X509Certificate::verify_signed_by_certificate`
+/// doesn't implement natively.
+pub(crate) fn is_signed_by(candidate_signer: &Rc<RefCell<CertKeyPair>>, cert: &Rc<RefCell<CertKeyPair>>) -> bool {
+    let signer_cert = &(*(**candidate_signer).borrow().distributed_cert).borrow().certificate.original;
+    let cert_cert = &(*(**cert).borrow().distributed_cert).borrow().certificate.original;
+
+    crypto_backend::active_backend().is_signed_by(signer_cert, cert_cert)
+}
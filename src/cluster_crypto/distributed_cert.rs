@@ -0,0 +1,127 @@
+use super::{certificate::Certificate, locations::Locations};
+
+pub(crate) struct DistributedCert {
+    pub(crate) certificate: Certificate,
+    pub(crate) locations: Locations,
+    /// Every extension that needs to survive regeneration byte-for-byte, as raw `(oid, critical,
+    /// DER value)` triples: every critical extension found on this cert at scan time (retained
+    /// verbatim rather than parsed into a first-class type), plus `SubjectAlternativeName` even
+    /// when it's marked non-critical (the common case) - regeneration would otherwise build a
+    /// fresh cert with no SANs at all. See [`Self::reapply_carried_extensions`].
+    pub(crate) carried_extensions: Vec<(x509_certificate::asn1time::ObjectIdentifier, bool, Vec<u8>)>,
+}
+
+const OID_NAME_CONSTRAINTS: &[u64] = &[2, 5, 29, 30];
+
+impl DistributedCert {
+    pub(crate) async fn commit_to_etcd_and_disk(&self, etcd_client: &crate::k8s_etcd::InMemoryK8sEtcd) {
+        for location in &self.locations.0 {
+            crate::k8s_etcd::commit_cert_at_location(etcd_client, location, &self.certificate).await;
+        }
+    }
+
+    /// Re-emit this cert's carried extensions onto a regenerated certificate's builder - critical
+    /// extensions (`NameConstraints` in particular) and `SubjectAlternativeName` - so a CA that
+    /// was scoped when we found it stays scoped the same way after regeneration, and a leaf cert
+    /// keeps the SANs it was issued with, each with the criticality it originally had.
+    pub(crate) fn reapply_carried_extensions(&self, builder: &mut openssl::x509::X509Builder) {
+        for (oid, critical, der_value) in &self.carried_extensions {
+            let asn1_object = openssl::asn1::Asn1Object::from_str(&oid.to_string()).expect("OID round-trips through its string form");
+            let extension =
+                openssl::x509::extension::Extension::new_from_der(asn1_object, *critical, der_value).expect("carried extension re-encodes cleanly");
+            builder.append_extension(extension).expect("failed to append re-emitted extension");
+        }
+    }
+
+    fn name_constraints(&self) -> Option<NameConstraints> {
+        self.carried_extensions
+            .iter()
+            .find(|(oid, _, _)| oid.as_ref() == OID_NAME_CONSTRAINTS)
+            .map(|(_, _, der_value)| NameConstraints::parse(der_value))
+    }
+
+    /// Check that `self`, once signed by `issuer`, still falls within whatever
+    /// `NameConstraints` permitted/excluded subtrees `issuer` was scoped with. Called right
+    /// after regeneration so a regenerated chain can never silently widen trust relative to the
+    /// scanned original.
+    pub(crate) fn validate_against_issuer_constraints(&self, issuer: &DistributedCert) -> Result<(), String> {
+        let Some(name_constraints) = issuer.name_constraints() else {
+            return Ok(());
+        };
+
+        for dns_san in self.certificate.dns_sans() {
+            if !name_constraints.permits_dns(&dns_san) {
+                return Err(format!(
+                    "regenerated cert at {} has SAN '{dns_san}' which violates the NameConstraints of its issuer at {}",
+                    self.locations, issuer.locations
+                ));
+            }
+        }
+
+        for ip_san in self.certificate.ip_sans() {
+            if !name_constraints.permits_ip(&ip_san) {
+                return Err(format!(
+                    "regenerated cert at {} has IP SAN '{ip_san:?}' which violates the NameConstraints of its issuer at {}",
+                    self.locations, issuer.locations
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A minimal decode of RFC 5280 §4.2.1.10 - just the DNS and IP `GeneralSubtree`s, which are the
+/// two forms recert's regenerated certs actually carry SANs for. Directory-name subtrees are
+/// still retained verbatim by `reapply_carried_extensions` (we never drop the extension), they're
+/// just not independently re-validated here.
+#[derive(Default)]
+pub(crate) struct NameConstraints {
+    pub(crate) permitted_dns: Vec<String>,
+    pub(crate) excluded_dns: Vec<String>,
+    pub(crate) permitted_ip: Vec<(std::net::IpAddr, std::net::IpAddr)>,
+    pub(crate) excluded_ip: Vec<(std::net::IpAddr, std::net::IpAddr)>,
+}
+
+impl NameConstraints {
+    fn parse(der_value: &[u8]) -> Self {
+        super::pem_utils::parse_name_constraints(der_value)
+    }
+
+    fn permits_dns(&self, name: &str) -> bool {
+        let excluded = self.excluded_dns.iter().any(|suffix| dns_subtree_matches(suffix, name));
+        if excluded {
+            return false;
+        }
+
+        self.permitted_dns.is_empty() || self.permitted_dns.iter().any(|suffix| dns_subtree_matches(suffix, name))
+    }
+
+    fn permits_ip(&self, addr: &std::net::IpAddr) -> bool {
+        let in_range = |(lo, hi): &(std::net::IpAddr, std::net::IpAddr)| ip_in_range(*addr, *lo, *hi);
+
+        if self.excluded_ip.iter().any(in_range) {
+            return false;
+        }
+
+        self.permitted_ip.is_empty() || self.permitted_ip.iter().any(in_range)
+    }
+}
+
+/// A `GeneralSubtree` DNS constraint matches the name itself or any subdomain of it (RFC 5280
+/// §4.2.1.10: "the DNS name `example.com` indicates `host1.example.com`", plus the exact name).
+fn dns_subtree_matches(suffix: &str, name: &str) -> bool {
+    name == suffix || name.ends_with(&format!(".{suffix}"))
+}
+
+fn ip_in_range(addr: std::net::IpAddr, lo: std::net::IpAddr, hi: std::net::IpAddr) -> bool {
+    match (addr, lo, hi) {
+        (std::net::IpAddr::V4(addr), std::net::IpAddr::V4(lo), std::net::IpAddr::V4(hi)) => {
+            u32::from(addr) >= u32::from(lo) && u32::from(addr) <= u32::from(hi)
+        }
+        (std::net::IpAddr::V6(addr), std::net::IpAddr::V6(lo), std::net::IpAddr::V6(hi)) => {
+            u128::from(addr) >= u128::from(lo) && u128::from(addr) <= u128::from(hi)
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,93 @@
+use std::hash::{Hash, Hasher};
+
+/// The NIST curves recert knows how to parse/regenerate, in addition to RSA. OpenShift ships
+/// P-256 service-account keys and P-384 etcd peer certs, so both are first-class here rather than
+/// RSA being the only representable key shape.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum EcCurve {
+    P256,
+    P384,
+}
+
+/// An EC private key is only fully identified by its curve *and* its secret scalar - two keys on
+/// different curves that happen to have the same scalar bytes are different keys, so both fields
+/// participate in equality/hashing.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct EcPrivateKey {
+    pub(crate) curve: EcCurve,
+    /// Big-endian secret scalar.
+    pub(crate) scalar: Vec<u8>,
+}
+
+/// An EC public key is identified by its curve and its SEC1 uncompressed point; this is exactly
+/// what ends up in the SPKI `subjectPublicKey` bit string alongside the
+/// `id-ecPublicKey`/named-curve `AlgorithmIdentifier`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct EcPublicKey {
+    pub(crate) curve: EcCurve,
+    pub(crate) point: Vec<u8>,
+}
+
+/// An RSA key (public or private half), identified purely by its PKCS#1 DER encoding. Kept as raw
+/// bytes rather than a backend-specific type (an `openssl::rsa::Rsa<...>` or an `rsa::RsaPublicKey`)
+/// so that `cluster_crypto`'s shared bookkeeping - hashmap keys, equality checks, JWT signer
+/// matching - doesn't itself depend on which `crypto_backend` is linked in. Each backend parses
+/// these bytes into its own native type right before it actually needs to do crypto with them.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct RsaKey(pub(crate) Vec<u8>);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum PrivateKey {
+    Rsa(RsaKey),
+    Ec(EcPrivateKey),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum PublicKey {
+    Rsa(RsaKey),
+    Ec(EcPublicKey),
+}
+
+/// Derive the public half of a private key: for RSA this is just `(n, e)`; for EC it's the base
+/// point of the key's curve multiplied by the secret scalar. Done with the `rsa`/`p256`/`p384`
+/// crates rather than `openssl` so this derivation doesn't depend on which `CryptoBackend` is
+/// active - it's shared bookkeeping (`associate_public_keys`, JWT signer matching), not signature
+/// verification.
+impl From<&PrivateKey> for PublicKey {
+    fn from(private_key: &PrivateKey) -> Self {
+        match private_key {
+            PrivateKey::Rsa(RsaKey(der)) => {
+                use rsa::pkcs1::{DecodeRsaPrivateKey, EncodeRsaPublicKey};
+                let private = rsa::RsaPrivateKey::from_pkcs1_der(der).expect("RSA private key was validated when it was parsed");
+                let public = rsa::RsaPublicKey::from(&private);
+                PublicKey::Rsa(RsaKey(public.to_pkcs1_der().expect("RSA public key re-encodes cleanly").as_bytes().to_vec()))
+            }
+            PrivateKey::Ec(ec) => PublicKey::Ec(EcPublicKey {
+                curve: ec.curve,
+                point: match ec.curve {
+                    EcCurve::P256 => {
+                        let scalar = left_pad(&ec.scalar, 32);
+                        let secret = p256::SecretKey::from_bytes(scalar.as_slice().into()).expect("scalar was validated when it was parsed");
+                        secret.public_key().to_encoded_point(false).as_bytes().to_vec()
+                    }
+                    EcCurve::P384 => {
+                        let scalar = left_pad(&ec.scalar, 48);
+                        let secret = p384::SecretKey::from_bytes(scalar.as_slice().into()).expect("scalar was validated when it was parsed");
+                        secret.public_key().to_encoded_point(false).as_bytes().to_vec()
+                    }
+                },
+            }),
+        }
+    }
+}
+
+/// Left-pad a big-endian scalar to exactly `field_size` bytes. `ec.scalar` is stored as the
+/// minimal-length big-endian encoding produced when we parsed it (leading zero bytes stripped,
+/// same as any bignum encoding), but `p256`/`p384::SecretKey::from_bytes` require input of exactly
+/// the curve's field size - about 1 in 256 P-256 keys (and 1 in 256 P-384 keys) have a scalar
+/// whose top byte happens to be zero, which would otherwise panic here.
+fn left_pad(scalar: &[u8], field_size: usize) -> Vec<u8> {
+    let mut padded = vec![0u8; field_size.saturating_sub(scalar.len())];
+    padded.extend_from_slice(scalar);
+    padded
+}
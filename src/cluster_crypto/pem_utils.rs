@@ -0,0 +1,134 @@
+//! Small hand-rolled DER helpers for the handful of things recert needs to pick apart that aren't
+//! exposed as first-class types by the `x509_certificate`/`openssl` crates - the key identifiers
+//! inside the Subject/Authority Key Identifier extensions, used for signer lookup, and the
+//! `NameConstraints` extension, whose `GeneralSubtree`s we need to walk to validate a regenerated
+//! chain against its issuer's scoping.
+
+/// A single BER/DER tag-length-value, plus whatever bytes followed it.
+struct Tlv<'a> {
+    tag: u8,
+    content: &'a [u8],
+    rest: &'a [u8],
+}
+
+fn read_tlv(bytes: &[u8]) -> Option<Tlv<'_>> {
+    let (&tag, rest) = bytes.split_first()?;
+    let (&len_byte, rest) = rest.split_first()?;
+
+    let (len, rest) = if len_byte & 0x80 == 0 {
+        (len_byte as usize, rest)
+    } else {
+        let num_len_bytes = (len_byte & 0x7f) as usize;
+        if rest.len() < num_len_bytes {
+            return None;
+        }
+        let (len_bytes, rest) = rest.split_at(num_len_bytes);
+        let len = len_bytes.iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+        (len, rest)
+    };
+
+    if rest.len() < len {
+        return None;
+    }
+    let (content, rest) = rest.split_at(len);
+
+    Some(Tlv { tag, content, rest })
+}
+
+/// Walk every top-level TLV in `bytes` (used for SEQUENCE contents, where each element is a
+/// sibling TLV rather than nested further).
+fn each_element(bytes: &[u8]) -> impl Iterator<Item = Tlv<'_>> {
+    std::iter::successors(read_tlv(bytes), |tlv| read_tlv(tlv.rest))
+}
+
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_DNS_NAME: u8 = 0x82; // [2] IMPLICIT IA5String
+const TAG_IP_ADDRESS: u8 = 0x87; // [7] IMPLICIT OCTET STRING
+const TAG_AKI_KEY_IDENTIFIER: u8 = 0x80; // [0] IMPLICIT KeyIdentifier, inside AuthorityKeyIdentifier
+
+/// `SubjectKeyIdentifier ::= KeyIdentifier` (RFC 5280 §4.2.1.2), i.e. the extnValue is just an
+/// `OCTET STRING` whose content *is* the key id. Extract those raw bytes out of the wrapper.
+pub(crate) fn subject_key_identifier_bytes(extn_value: &[u8]) -> Option<Vec<u8>> {
+    read_tlv(extn_value).filter(|tlv| tlv.tag == TAG_OCTET_STRING).map(|tlv| tlv.content.to_vec())
+}
+
+/// `AuthorityKeyIdentifier ::= SEQUENCE { keyIdentifier [0] IMPLICIT KeyIdentifier OPTIONAL, ... }`
+/// (RFC 5280 §4.2.1.1). Extract the `[0]` `keyIdentifier` field's raw bytes, if present - this is
+/// the only choice we key signer lookups on, since it's what's directly comparable to a
+/// `SubjectKeyIdentifier`.
+pub(crate) fn authority_key_identifier_bytes(extn_value: &[u8]) -> Option<Vec<u8>> {
+    let outer = read_tlv(extn_value).filter(|tlv| tlv.tag == TAG_SEQUENCE)?;
+    each_element(outer.content)
+        .find(|tlv| tlv.tag == TAG_AKI_KEY_IDENTIFIER)
+        .map(|tlv| tlv.content.to_vec())
+}
+const TAG_PERMITTED_SUBTREES: u8 = 0xa0; // [0]
+const TAG_EXCLUDED_SUBTREES: u8 = 0xa1; // [1]
+
+pub(crate) fn parse_name_constraints(der: &[u8]) -> super::distributed_cert::NameConstraints {
+    use super::distributed_cert::NameConstraints;
+
+    let mut name_constraints = NameConstraints::default();
+
+    let Some(outer) = read_tlv(der) else { return name_constraints };
+    if outer.tag != TAG_SEQUENCE {
+        return name_constraints;
+    }
+
+    for subtrees_field in each_element(outer.content) {
+        let (dns, ip) = match subtrees_field.tag {
+            TAG_PERMITTED_SUBTREES => (&mut name_constraints.permitted_dns, &mut name_constraints.permitted_ip),
+            TAG_EXCLUDED_SUBTREES => (&mut name_constraints.excluded_dns, &mut name_constraints.excluded_ip),
+            _ => continue,
+        };
+
+        for general_subtree in each_element(subtrees_field.content) {
+            if general_subtree.tag != TAG_SEQUENCE {
+                continue;
+            }
+
+            // GeneralSubtree ::= SEQUENCE { base GeneralName, minimum/maximum ignored }. The
+            // `base` is always the first element.
+            let Some(base) = each_element(general_subtree.content).next() else {
+                continue;
+            };
+
+            match base.tag {
+                TAG_DNS_NAME => {
+                    if let Ok(name) = std::str::from_utf8(base.content) {
+                        dns.push(name.to_string());
+                    }
+                }
+                TAG_IP_ADDRESS => {
+                    if let Some(range) = parse_ip_subtree(base.content) {
+                        ip.push(range);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    name_constraints
+}
+
+/// An IP `GeneralName` in a name constraint is `address || mask`, both 4 bytes (IPv4) or both 16
+/// bytes (IPv6). We turn the mask into an inclusive `(low, high)` range.
+fn parse_ip_subtree(content: &[u8]) -> Option<(std::net::IpAddr, std::net::IpAddr)> {
+    match content.len() {
+        8 => {
+            let (addr, mask) = content.split_at(4);
+            let addr = u32::from_be_bytes(addr.try_into().ok()?);
+            let mask = u32::from_be_bytes(mask.try_into().ok()?);
+            Some((std::net::IpAddr::from((addr & mask).to_be_bytes()), std::net::IpAddr::from((addr | !mask).to_be_bytes())))
+        }
+        32 => {
+            let (addr, mask) = content.split_at(16);
+            let addr = u128::from_be_bytes(addr.try_into().ok()?);
+            let mask = u128::from_be_bytes(mask.try_into().ok()?);
+            Some((std::net::IpAddr::from((addr & mask).to_be_bytes()), std::net::IpAddr::from((addr | !mask).to_be_bytes())))
+        }
+        _ => None,
+    }
+}